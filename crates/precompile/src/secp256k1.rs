@@ -1,20 +1,63 @@
+//! The `ECRECOVER` precompile. The `k256` backend is pure Rust and `no_std`
+//! (plus `alloc`) compatible, so it doubles as the fallback for embedded and
+//! wasm targets where the C-backed `secp256k1`/`libsecp256k1` backends and
+//! the `std`-only provider registry/batch APIs below aren't available.
+
 use crate::{
     utilities::right_pad, PrecompileError, PrecompileOutput, PrecompileResult,
     PrecompileWithAddress,
 };
 use primitives::{alloy_primitives::B512, Bytes, B256};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
 
 pub const ECRECOVER: PrecompileWithAddress =
     PrecompileWithAddress(crate::u64_to_address(1), ec_recover_run);
 
 pub use self::secp256k1::ecrecover;
+pub use recovery::{recover_address, recover_public_key, RecoveryError};
+#[cfg(any(test, feature = "sign-recoverable"))]
+pub use recovery::sign_recoverable;
+
+mod recovery;
+
+/// A pluggable backend for the `ECRECOVER` precompile.
+///
+/// Implement this to swap in a hardware-accelerated, precomputed-table, or
+/// remote-attestation recovery backend without recompiling revm. Install an
+/// instance with [`set_ec_recover_provider`]; until one is installed,
+/// [`ec_recover_run`] falls back to the built-in `secp256k1`/`libsecp256k1`/
+/// `k256` implementation selected at compile time.
+///
+/// Requires `std`: the registry is backed by [`OnceLock`], which `core`/
+/// `alloc` don't provide.
+#[cfg(feature = "std")]
+pub trait EcRecoverProvider: Send + Sync {
+    /// Recovers the signer's keccak-truncated address (left-padded to 32
+    /// bytes), or `None` if `sig`/`recid` do not recover to a valid key.
+    fn recover(&self, sig: &B512, recid: u8, msg: &B256) -> Option<B256>;
+}
+
+#[cfg(feature = "std")]
+static EC_RECOVER_PROVIDER: OnceLock<Box<dyn EcRecoverProvider>> = OnceLock::new();
+
+/// Installs a custom [`EcRecoverProvider`] for the `ECRECOVER` precompile.
+///
+/// The provider can only be installed once; subsequent calls return the
+/// rejected provider in `Err` instead of replacing the existing one.
+#[cfg(feature = "std")]
+pub fn set_ec_recover_provider(
+    provider: Box<dyn EcRecoverProvider>,
+) -> Result<(), Box<dyn EcRecoverProvider>> {
+    EC_RECOVER_PROVIDER.set(provider)
+}
 
 #[allow(clippy::module_inception)]
 mod secp256k1 {
     use primitives::{alloy_primitives::B512, keccak256, B256};
 
     cfg_if::cfg_if! {
-        if #[cfg(feature = "secp256k1")] {
+        if #[cfg(all(feature = "secp256k1", feature = "std"))] {
             use secp256k1::{
                 ecdsa::{RecoverableSignature, RecoveryId},
                 Message, SECP256K1,
@@ -34,7 +77,32 @@ mod secp256k1 {
                 hash[..12].fill(0);
                 Ok(hash)
             }
-        } else if #[cfg(feature = "libsecp256k1")] {
+
+            pub fn recover_public_key(
+                sig: &B512,
+                recid: u8,
+                msg: &B256,
+            ) -> Result<B512, secp256k1::Error> {
+                let recid = RecoveryId::from_i32(recid as i32).expect("recovery ID is valid");
+                let sig = RecoverableSignature::from_compact(sig.as_slice(), recid)?;
+
+                let msg = Message::from_digest(msg.0);
+                let public = SECP256K1.recover_ecdsa(&msg, &sig)?;
+
+                Ok(B512::from_slice(&public.serialize_uncompressed()[1..]))
+            }
+
+            #[cfg(any(test, feature = "sign-recoverable"))]
+            pub fn sign_recoverable(msg: &B256, secret: &B256) -> (u8, B512) {
+                let secret_key =
+                    secp256k1::SecretKey::from_slice(secret.as_slice()).expect("valid secret key");
+                let msg = Message::from_digest(msg.0);
+                let (recid, sig) = SECP256K1
+                    .sign_ecdsa_recoverable(&msg, &secret_key)
+                    .serialize_compact();
+                (recid.to_i32() as u8, B512::from_slice(&sig))
+            }
+        } else if #[cfg(all(feature = "libsecp256k1", feature = "std"))] {
             pub fn ecrecover(sig: &B512, recid: u8, msg: &B256) -> Result<B256, libsecp256k1::Error> {
                 let recid = libsecp256k1::RecoveryId::parse(recid)?;
                 let sig = RecoverableSignature::from_compact(sig.as_slice(), recid)?;
@@ -46,7 +114,33 @@ mod secp256k1 {
                 hash[..12].fill(0);
                 Ok(hash)
             }
+
+            pub fn recover_public_key(
+                sig: &B512,
+                recid: u8,
+                msg: &B256,
+            ) -> Result<B512, libsecp256k1::Error> {
+                let recid = libsecp256k1::RecoveryId::parse(recid)?;
+                let sig = RecoverableSignature::from_compact(sig.as_slice(), recid)?;
+
+                let msg = libsecp256k1::Message::parse(msg.as_ref());
+                let public = libsecp256k1::recover(&msg, &sig, &recid)?;
+
+                Ok(B512::from_slice(&public.serialize()[1..]))
+            }
+
+            #[cfg(any(test, feature = "sign-recoverable"))]
+            pub fn sign_recoverable(msg: &B256, secret: &B256) -> (u8, B512) {
+                let secret_key = libsecp256k1::SecretKey::parse_slice(secret.as_slice())
+                    .expect("valid secret key");
+                let msg = libsecp256k1::Message::parse(msg.as_ref());
+                let (sig, recid) = libsecp256k1::sign(&msg, &secret_key);
+                (recid.serialize(), B512::from_slice(&sig.serialize()))
+            }
         } else {
+            // Pure Rust and `no_std` (+ `alloc`) compatible: the automatic
+            // fallback whenever `std` is off, regardless of which of
+            // `secp256k1`/`libsecp256k1` is also enabled.
             use k256::ecdsa::{Error, RecoveryId, Signature, VerifyingKey};
 
             pub fn ecrecover(sig: &B512, mut recid: u8, msg: &B256) -> Result<B256, Error> {
@@ -73,6 +167,107 @@ mod secp256k1 {
                 hash[..12].fill(0);
                 Ok(hash)
             }
+
+            pub fn recover_public_key(sig: &B512, mut recid: u8, msg: &B256) -> Result<B512, Error> {
+                // parse signature
+                let mut sig = Signature::from_slice(sig.as_slice())?;
+
+                // normalize signature and flip recovery id if needed.
+                if let Some(sig_normalized) = sig.normalize_s() {
+                    sig = sig_normalized;
+                    recid ^= 1;
+                }
+                let recid = RecoveryId::from_byte(recid).expect("recovery ID is valid");
+
+                // recover key
+                let recovered_key = VerifyingKey::recover_from_prehash(&msg[..], &sig, recid)?;
+
+                Ok(B512::from_slice(
+                    &recovered_key.to_encoded_point(/* compress = */ false).as_bytes()[1..],
+                ))
+            }
+
+            #[cfg(any(test, feature = "sign-recoverable"))]
+            pub fn sign_recoverable(msg: &B256, secret: &B256) -> (u8, B512) {
+                let signing_key = k256::ecdsa::SigningKey::from_bytes(secret.as_ref().into())
+                    .expect("valid secret key");
+                let (sig, recid) = signing_key
+                    .sign_prehash_recoverable(&msg[..])
+                    .expect("signing succeeds");
+                (recid.to_byte(), B512::from_slice(&sig.to_bytes()))
+            }
+        }
+    }
+}
+
+/// Whether `recid` is in the valid ECDSA recovery id range (`0..=3`).
+///
+/// Every backend's `RecoveryId::from_byte`/`from_i32`/`parse` either panics
+/// (`secp256k1`, `k256`) or errors (`libsecp256k1`) outside this range.
+/// `ec_recover_run`'s own input decoding only ever produces 0 or 1, but
+/// [`ec_recover_batch`] takes `recid` straight from its caller, so it must
+/// check this itself before reaching an `.expect()`.
+fn is_valid_recid(recid: u8) -> bool {
+    recid < 4
+}
+
+/// Recovers one `(sig, recid, msg)` triple via the installed
+/// [`EcRecoverProvider`] if one is set, otherwise the built-in backend.
+#[cfg(feature = "std")]
+fn recover_one(sig: &B512, recid: u8, msg: &B256) -> Option<B256> {
+    match EC_RECOVER_PROVIDER.get() {
+        Some(provider) => provider.recover(sig, recid, msg),
+        None => is_valid_recid(recid)
+            .then(|| secp256k1::ecrecover(sig, recid, msg).ok())
+            .flatten(),
+    }
+}
+
+/// Recovers the signer addresses for a batch of `(signature, recovery id,
+/// message)` triples, reusing a single secp256k1 context and deduplicating
+/// identical triples across the whole batch.
+///
+/// `ec_recover_run` is a thin wrapper over this with a one-element slice;
+/// callers that recover every transaction sender in a block (reth and
+/// friends) should call this directly instead of looping over `ecrecover`,
+/// since repeated signatures (common in batch-signed/meta transactions) would
+/// otherwise be recovered more than once. With the `secp256k1` backend every
+/// recovery, batched or not, uses the one global `SECP256K1` context, so no
+/// context is built per call on either path. Each triple goes through the
+/// installed [`EcRecoverProvider`] if one is set, same as [`ec_recover_run`],
+/// so an embedder's custom backend applies to the batch path too. An
+/// out-of-range recovery id recovers to `None` rather than panicking.
+///
+/// Requires `std` for `HashMap`/`HashSet`; under `no_std` callers fall back
+/// to calling [`ecrecover`] directly via [`ec_recover_run`].
+#[cfg(feature = "std")]
+pub fn ec_recover_batch(inputs: &[(B512, u8, B256)]) -> Vec<Option<B256>> {
+    use std::collections::HashMap;
+
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "rayon")] {
+            use rayon::prelude::*;
+            use std::collections::HashSet;
+
+            let unique: HashSet<(B512, u8, B256)> = inputs.iter().copied().collect();
+            let recovered: HashMap<(B512, u8, B256), Option<B256>> = unique
+                .into_par_iter()
+                .map(|key| {
+                    let out = recover_one(&key.0, key.1, &key.2);
+                    (key, out)
+                })
+                .collect();
+            inputs.iter().map(|key| recovered[key]).collect()
+        } else {
+            let mut cache: HashMap<(B512, u8, B256), Option<B256>> = HashMap::new();
+            inputs
+                .iter()
+                .map(|key| {
+                    *cache
+                        .entry(*key)
+                        .or_insert_with(|| recover_one(&key.0, key.1, &key.2))
+                })
+                .collect()
         }
     }
 }
@@ -95,8 +290,111 @@ pub fn ec_recover_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let recid = input[63] - 27;
     let sig = <&B512>::try_from(&input[64..128]).unwrap();
 
-    let out = secp256k1::ecrecover(sig, recid, msg)
-        .map(|o| o.to_vec().into())
-        .unwrap_or_default();
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")] {
+            // Fall back to the built-in backend only when no provider is
+            // installed. A `None` from an *installed* provider means "does
+            // not recover to a valid key" and must not be second-guessed by
+            // re-running the built-in implementation.
+            let recovered = match EC_RECOVER_PROVIDER.get() {
+                Some(provider) => provider.recover(sig, recid, msg),
+                None => ec_recover_batch(&[(*sig, recid, *msg)])[0],
+            };
+        } else {
+            let recovered = secp256k1::ecrecover(sig, recid, msg).ok();
+        }
+    }
+
+    let out = recovered.map(|o| o.to_vec().into()).unwrap_or_default();
     Ok(PrecompileOutput::new(ECRECOVER_BASE, out))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::keccak256;
+
+    /// `sign_recoverable` returns whatever recovery id a secret/message pair
+    /// happens to produce; find a pair whose `v` is the 0/1 that
+    /// `ec_recover_run`'s plain `27`/`28` encoding supports, by trying a
+    /// small fixed set of candidates (no randomness, so the test stays
+    /// deterministic).
+    fn low_recid_fixture() -> (B256, B256, u8, B512) {
+        for seed in 1u8..=16 {
+            let secret = B256::from_slice(&[seed; 32]);
+            let msg = keccak256([seed]);
+            let (recid, sig) = sign_recoverable(&msg, &secret);
+            if recid < 2 {
+                return (msg, secret, recid, sig);
+            }
+        }
+        panic!("no low-recid fixture found among the candidates");
+    }
+
+    #[test]
+    fn sign_then_recover_round_trips() {
+        let (msg, _secret, recid, sig) = low_recid_fixture();
+
+        let address = recover_address(&sig, recid, &msg).expect("valid signature recovers");
+        let public_key = recover_public_key(&sig, recid, &msg).expect("valid signature recovers");
+
+        let mut expected_address = keccak256(public_key.as_slice());
+        expected_address[..12].fill(0);
+        assert_eq!(address, expected_address);
+    }
+
+    #[test]
+    fn ec_recover_run_matches_recover_address() {
+        let (msg, _secret, recid, sig) = low_recid_fixture();
+        let address = recover_address(&sig, recid, &msg).expect("valid signature recovers");
+
+        let mut input = [0u8; 128];
+        input[0..32].copy_from_slice(msg.as_slice());
+        input[63] = 27 + recid;
+        input[64..128].copy_from_slice(sig.as_slice());
+
+        let output =
+            ec_recover_run(&Bytes::copy_from_slice(&input), 3_000).expect("precompile succeeds");
+        assert_eq!(output.bytes.as_ref(), address.as_slice());
+    }
+
+    #[test]
+    fn recover_rejects_out_of_range_recid() {
+        let (msg, _secret, _recid, sig) = low_recid_fixture();
+
+        assert_eq!(recover_address(&sig, 4, &msg), Err(RecoveryError));
+        assert_eq!(recover_public_key(&sig, 4, &msg), Err(RecoveryError));
+    }
+
+    /// A signature/message/address triple computed independently of this
+    /// crate (plain-Python modular arithmetic over secp256k1, not this
+    /// file's `k256`/`secp256k1`/`libsecp256k1` code). The assertion doesn't
+    /// depend on which backend is compiled in, so running this same test
+    /// under a native target (the `secp256k1`/`libsecp256k1` backend) and
+    /// under a `no_std` `wasm32-unknown-unknown` build (the `k256`
+    /// fallback) is what verifies the precompile produces identical output
+    /// on wasm and native.
+    #[test]
+    fn ecrecover_matches_known_test_vector() {
+        let msg = B256::from_slice(&[
+            0x3f, 0x8a, 0x41, 0x24, 0xe9, 0x46, 0x0a, 0x14, 0xb1, 0xe3, 0xd8, 0xcc, 0x63, 0x6e,
+            0x97, 0x1c, 0x90, 0xfb, 0x21, 0x3e, 0x99, 0xc3, 0xa0, 0xca, 0x7d, 0xbc, 0x59, 0xda,
+            0x8f, 0x5a, 0xf2, 0x42,
+        ]);
+        let sig = B512::from_slice(&[
+            0xb6, 0xab, 0x48, 0xc6, 0xfe, 0xeb, 0xd3, 0x90, 0x76, 0x65, 0x08, 0x61, 0xdf, 0x05,
+            0xaf, 0xc9, 0x64, 0xf5, 0xf6, 0xad, 0xd6, 0x7f, 0x0c, 0x71, 0xdd, 0x89, 0xa4, 0xef,
+            0x9a, 0x72, 0x98, 0x7a, 0x61, 0xc1, 0x80, 0xf9, 0x95, 0x14, 0xa9, 0xcf, 0x9d, 0x44,
+            0xbe, 0xd4, 0xc9, 0x03, 0xdc, 0x1f, 0x37, 0x42, 0xa8, 0x1e, 0xf9, 0xe3, 0x1f, 0xcb,
+            0x2a, 0x6a, 0xef, 0xe6, 0x10, 0x79, 0x84, 0x75,
+        ]);
+        let recid = 1u8;
+        let expected_address = B256::from_slice(&[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x17, 0xc5,
+            0x18, 0x51, 0x67, 0x40, 0x1e, 0xd0, 0x0c, 0xf5, 0xf5, 0xb2, 0xfc, 0x97, 0xd9, 0xbb,
+            0xfd, 0xb7, 0xd0, 0x25,
+        ]);
+
+        assert_eq!(recover_address(&sig, recid, &msg), Ok(expected_address));
+    }
+}
@@ -0,0 +1,61 @@
+//! Full public-key recovery, split out of the `ECRECOVER` precompile so
+//! downstream crates can get at the key material directly instead of only
+//! the keccak-truncated address.
+//!
+//! `no_std` compatible: all functions here bottom out in the `k256` backend
+//! when `std`/`secp256k1`/`libsecp256k1` are off.
+
+use super::secp256k1;
+use primitives::{alloy_primitives::B512, B256};
+
+/// A signature, recovery id, or message failed to recover a public key.
+///
+/// The underlying backends (`secp256k1`, `libsecp256k1`, `k256`) each have
+/// their own error type; this flattens them since callers only ever care
+/// whether recovery succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryError;
+
+impl core::fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("public key recovery failed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecoveryError {}
+
+/// Recovers the signer's full uncompressed public key (64 bytes, without the
+/// leading `0x04` tag) from `sig`/`recid` over `msg`.
+///
+/// Use [`recover_address`] instead if only the keccak-truncated address is
+/// needed, e.g. to back the `ECRECOVER` precompile.
+pub fn recover_public_key(sig: &B512, recid: u8, msg: &B256) -> Result<B512, RecoveryError> {
+    // The backends' `RecoveryId::from_byte`/`from_i32` panic outside
+    // `0..=3`; reject out-of-range ids here instead of forwarding them.
+    if !super::is_valid_recid(recid) {
+        return Err(RecoveryError);
+    }
+    secp256k1::recover_public_key(sig, recid, msg).map_err(|_| RecoveryError)
+}
+
+/// Recovers the signer's 20-byte address, left-padded to 32 bytes, as
+/// returned by the `ECRECOVER` precompile.
+pub fn recover_address(sig: &B512, recid: u8, msg: &B256) -> Result<B256, RecoveryError> {
+    if !super::is_valid_recid(recid) {
+        return Err(RecoveryError);
+    }
+    secp256k1::ecrecover(sig, recid, msg).map_err(|_| RecoveryError)
+}
+
+/// Signs `msg` with `secret`, returning the recovery id and compact `(r, s)`
+/// signature that [`recover_address`]/[`recover_public_key`] and the
+/// `ECRECOVER` precompile expect as input.
+///
+/// Gated behind `cfg(test)`/the `sign-recoverable` feature: it exists so
+/// downstream crates and tests can construct valid ECRECOVER inputs without
+/// pulling in a second `secp256k1` dependency, not for production signing.
+#[cfg(any(test, feature = "sign-recoverable"))]
+pub fn sign_recoverable(msg: &B256, secret: &B256) -> (u8, B512) {
+    secp256k1::sign_recoverable(msg, secret)
+}